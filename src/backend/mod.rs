@@ -0,0 +1,74 @@
+//! The elliptic-curve backend behind `PrivateKey`/`Signature`'s address
+//! derivation, signing and recovery paths.
+//!
+//! The default backend links the C `secp256k1` library. Building with
+//! the `k256` feature instead selects a pure-Rust implementation, which
+//! is friendlier to `wasm32` and `no_std` targets that can't link a C
+//! dependency. Both backends expose the same functions, so
+//! `private_key` and `signature` stay oblivious to which one is active.
+
+use failure::Error;
+
+#[cfg(not(feature = "k256"))]
+mod secp;
+#[cfg(not(feature = "k256"))]
+use self::secp as imp;
+
+#[cfg(feature = "k256")]
+mod pure_rust;
+#[cfg(feature = "k256")]
+use self::pure_rust as imp;
+
+/// `(r, s, recovery_id)` of a recoverable ECDSA signature, with `s`
+/// already normalized to low-`s` and `recovery_id` flipped to match.
+pub struct RawSignature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub recovery_id: i32,
+}
+
+/// Derives the uncompressed public key (`0x04 || x || y`) for
+/// `private_key`, rejecting the all-zero key.
+pub fn public_key_bytes(private_key: &[u8; 32]) -> Result<[u8; 65], Error> {
+    imp::public_key_bytes(private_key)
+}
+
+/// Produces a recoverable ECDSA signature over `hash` with `private_key`.
+pub fn sign_recoverable(private_key: &[u8; 32], hash: &[u8; 32]) -> RawSignature {
+    imp::sign_recoverable(private_key, hash)
+}
+
+/// Recovers the uncompressed public key that produced `(r, s,
+/// recovery_id)` over `hash`.
+pub fn recover_public_key(
+    hash: &[u8; 32],
+    r: &[u8; 32],
+    s: &[u8; 32],
+    recovery_id: i32,
+) -> Result<[u8; 65], Error> {
+    imp::recover_public_key(hash, r, s, recovery_id)
+}
+
+/// Derives the *compressed* public key (`serP` in BIP-32 terms) for
+/// `private_key`.
+pub fn public_key_compressed(private_key: &[u8; 32]) -> Result<[u8; 33], Error> {
+    imp::public_key_compressed(private_key)
+}
+
+/// Computes the ECDH shared secret's x-coordinate between `private_key`
+/// and `peer_public_key` (an uncompressed, 65-byte point).
+pub fn ecdh_shared_secret(
+    private_key: &[u8; 32],
+    peer_public_key: &[u8; 65],
+) -> Result<[u8; 32], Error> {
+    imp::ecdh_shared_secret(private_key, peer_public_key)
+}
+
+/// Adds `tweak * G` to the compressed point `public_key`, as used by
+/// BIP-32's public-only CKD for normal (non-hardened) children.
+pub fn tweak_add_compressed_public_key(
+    public_key: &[u8; 33],
+    tweak: &[u8; 32],
+) -> Result<[u8; 33], Error> {
+    imp::tweak_add_compressed_public_key(public_key, tweak)
+}
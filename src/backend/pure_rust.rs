@@ -0,0 +1,161 @@
+//! The `k256` feature's backend: a pure-Rust secp256k1 implementation
+//! with no C dependency, suitable for `wasm32` and `no_std`-friendly
+//! targets.
+
+use super::RawSignature;
+use error::ClarityError;
+use failure::Error;
+use k256::ecdsa::recoverable;
+use k256::ecdsa::signature::digest::generic_array::GenericArray;
+use k256::ecdsa::signature::digest::Digest;
+use k256::ecdsa::signature::DigestSigner;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::{AffinePoint, EncodedPoint, ProjectivePoint, PublicKey, Scalar, SecretKey};
+use sha3::Keccak256;
+use std::convert::TryFrom;
+
+pub fn public_key_bytes(private_key: &[u8; 32]) -> Result<[u8; 65], Error> {
+    let sk = SecretKey::from_bytes(private_key).map_err(|_| ClarityError::InvalidPrivKey)?;
+    let point = PublicKey::from_secret_scalar(&sk.to_secret_scalar()).to_encoded_point(false);
+    let bytes = point.as_bytes();
+
+    if bytes[1..].iter().all(|b| *b == 0) {
+        return Err(ClarityError::ZeroPrivKey.into());
+    }
+
+    let mut out = [0u8; 65];
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+pub fn sign_recoverable(private_key: &[u8; 32], hash: &[u8; 32]) -> RawSignature {
+    let signing_key =
+        recoverable::SigningKey::from_bytes(private_key).expect("Invalid private key");
+    // `k256`'s recoverable signatures are already low-s normalized.
+    let sig: recoverable::Signature = signing_key
+        .try_sign_digest(FixedDigest(*hash))
+        .expect("Signing failed");
+
+    let bytes = sig.as_ref();
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&bytes[0..32]);
+    s.copy_from_slice(&bytes[32..64]);
+
+    let recovery_id = i32::from(bytes[64]);
+
+    RawSignature { r, s, recovery_id }
+}
+
+pub fn recover_public_key(
+    hash: &[u8; 32],
+    r: &[u8; 32],
+    s: &[u8; 32],
+    recovery_id: i32,
+) -> Result<[u8; 65], Error> {
+    let mut compact = [0u8; 65];
+    compact[0..32].copy_from_slice(r);
+    compact[32..64].copy_from_slice(s);
+    compact[64] = recovery_id as u8;
+
+    let sig = recoverable::Signature::try_from(&compact[..])
+        .map_err(|_| ClarityError::InvalidSignature)?;
+    let verify_key = sig
+        .recover_verify_key_from_digest(FixedDigest(*hash))
+        .map_err(|_| ClarityError::InvalidSignature)?;
+
+    let point = EncodedPoint::from(&verify_key)
+        .decompress()
+        .ok_or(ClarityError::InvalidSignature)?;
+
+    let mut out = [0u8; 65];
+    out.copy_from_slice(point.as_bytes());
+    Ok(out)
+}
+
+pub fn public_key_compressed(private_key: &[u8; 32]) -> Result<[u8; 33], Error> {
+    let sk = SecretKey::from_bytes(private_key).map_err(|_| ClarityError::InvalidPrivKey)?;
+    let point = PublicKey::from_secret_scalar(&sk.to_secret_scalar()).to_encoded_point(true);
+
+    let mut out = [0u8; 33];
+    out.copy_from_slice(point.as_bytes());
+    Ok(out)
+}
+
+pub fn ecdh_shared_secret(
+    private_key: &[u8; 32],
+    peer_public_key: &[u8; 65],
+) -> Result<[u8; 32], Error> {
+    let sk = SecretKey::from_bytes(private_key).map_err(|_| ClarityError::InvalidPrivKey)?;
+
+    let peer_point = EncodedPoint::from_bytes(&peer_public_key[..])
+        .map_err(|_| ClarityError::InvalidSignature)?;
+    let peer_affine = AffinePoint::from_encoded_point(&peer_point)
+        .ok_or(ClarityError::InvalidSignature)?;
+
+    let shared_point = ProjectivePoint::from(peer_affine) * sk.to_secret_scalar().as_ref();
+    let shared_encoded = shared_point.to_affine().to_encoded_point(false);
+
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&shared_encoded.as_bytes()[1..33]);
+    Ok(x)
+}
+
+pub fn tweak_add_compressed_public_key(
+    public_key: &[u8; 33],
+    tweak: &[u8; 32],
+) -> Result<[u8; 33], Error> {
+    let point = EncodedPoint::from_bytes(&public_key[..])
+        .map_err(|_| ClarityError::InvalidSignature)?;
+    let affine = AffinePoint::from_encoded_point(&point).ok_or(ClarityError::InvalidSignature)?;
+
+    let tweak_scalar: Option<Scalar> = Scalar::from_repr((*tweak).into());
+    let tweak_scalar = tweak_scalar.ok_or(ClarityError::InvalidSignature)?;
+
+    let result = ProjectivePoint::from(affine) + (ProjectivePoint::generator() * tweak_scalar);
+    let encoded = result.to_affine().to_encoded_point(true);
+
+    let mut out = [0u8; 33];
+    out.copy_from_slice(encoded.as_bytes());
+    Ok(out)
+}
+
+/// A `Digest` impl that treats its input as an already-computed 32-byte
+/// hash rather than hashing it, so `k256`'s `*_digest` signing APIs can
+/// be used on a hash this crate already computed (e.g. via Keccak256).
+#[derive(Clone)]
+struct FixedDigest([u8; 32]);
+
+impl Digest for FixedDigest {
+    type OutputSize = <Keccak256 as Digest>::OutputSize;
+
+    fn new() -> Self {
+        FixedDigest([0u8; 32])
+    }
+
+    fn input<B: AsRef<[u8]>>(&mut self, _data: B) {
+        unreachable!("FixedDigest is only ever constructed pre-filled")
+    }
+
+    fn chain<B: AsRef<[u8]>>(self, _data: B) -> Self {
+        self
+    }
+
+    fn result(self) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&self.0)
+    }
+
+    fn result_reset(&mut self) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&self.0)
+    }
+
+    fn reset(&mut self) {}
+
+    fn output_size() -> usize {
+        32
+    }
+
+    fn digest(data: &[u8]) -> GenericArray<u8, Self::OutputSize> {
+        GenericArray::clone_from_slice(&Keccak256::digest(data))
+    }
+}
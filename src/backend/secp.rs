@@ -0,0 +1,102 @@
+//! The default backend: the C `secp256k1` library via the `secp256k1`
+//! crate bindings.
+
+use super::RawSignature;
+use constants::SECPK1N;
+use error::ClarityError;
+use failure::Error;
+use num256::Uint256;
+use secp256k1::{Message, PublicKey, RecoverableSignature, RecoveryId, Secp256k1, SecretKey};
+
+pub fn public_key_bytes(private_key: &[u8; 32]) -> Result<[u8; 65], Error> {
+    let secp256k1 = Secp256k1::new();
+    let sk = SecretKey::from_slice(&secp256k1, private_key)?;
+    let pkey = PublicKey::from_secret_key(&secp256k1, &sk);
+
+    let pkey = pkey.serialize_uncompressed();
+    assert_eq!(pkey.len(), 65);
+    if pkey[1..].to_vec() == [0x00u8; 64].to_vec() {
+        return Err(ClarityError::ZeroPrivKey.into());
+    }
+    Ok(pkey)
+}
+
+pub fn sign_recoverable(private_key: &[u8; 32], hash: &[u8; 32]) -> RawSignature {
+    let secp256k1 = Secp256k1::new();
+    let sk = SecretKey::from_slice(&secp256k1, private_key).expect("Invalid private key");
+    let msg = Message::from_slice(hash).expect("Hashes are always 32 bytes");
+
+    let recoverable_sig = secp256k1.sign_recoverable(&msg, &sk);
+    let (recovery_id, sig_bytes) = recoverable_sig.serialize_compact(&secp256k1);
+
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&sig_bytes[0..32]);
+    s.copy_from_slice(&sig_bytes[32..64]);
+
+    let mut s_uint = Uint256::from_bytes_be(&s);
+    let mut recovery_id = recovery_id.to_i32();
+
+    // Normalize to low-s per EIP-2 / Homestead.
+    let half_n = SECPK1N.clone() / Uint256::from(2u32);
+    if s_uint > half_n {
+        s_uint = SECPK1N.clone() - s_uint;
+        recovery_id ^= 1;
+        s = s_uint.into();
+    }
+
+    RawSignature { r, s, recovery_id }
+}
+
+pub fn recover_public_key(
+    hash: &[u8; 32],
+    r: &[u8; 32],
+    s: &[u8; 32],
+    recovery_id: i32,
+) -> Result<[u8; 65], Error> {
+    let secp256k1 = Secp256k1::new();
+    let recovery_id = RecoveryId::from_i32(recovery_id)?;
+
+    let mut compact_sig = [0u8; 64];
+    compact_sig[0..32].copy_from_slice(r);
+    compact_sig[32..64].copy_from_slice(s);
+
+    let recoverable_sig =
+        RecoverableSignature::from_compact(&secp256k1, &compact_sig, recovery_id)?;
+    let msg = Message::from_slice(hash)?;
+    let pubkey = secp256k1.recover(&msg, &recoverable_sig)?;
+    Ok(pubkey.serialize_uncompressed())
+}
+
+pub fn public_key_compressed(private_key: &[u8; 32]) -> Result<[u8; 33], Error> {
+    let secp256k1 = Secp256k1::new();
+    let sk = SecretKey::from_slice(&secp256k1, private_key)?;
+    let pkey = PublicKey::from_secret_key(&secp256k1, &sk);
+    Ok(pkey.serialize())
+}
+
+pub fn ecdh_shared_secret(
+    private_key: &[u8; 32],
+    peer_public_key: &[u8; 65],
+) -> Result<[u8; 32], Error> {
+    let secp256k1 = Secp256k1::new();
+    let sk = SecretKey::from_slice(&secp256k1, private_key)?;
+
+    let mut point = PublicKey::from_slice(&secp256k1, peer_public_key)?;
+    point.mul_assign(&secp256k1, &sk[..])?;
+    let serialized = point.serialize_uncompressed();
+
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&serialized[1..33]);
+    Ok(x)
+}
+
+pub fn tweak_add_compressed_public_key(
+    public_key: &[u8; 33],
+    tweak: &[u8; 32],
+) -> Result<[u8; 33], Error> {
+    let secp256k1 = Secp256k1::new();
+    let mut pkey = PublicKey::from_slice(&secp256k1, public_key)?;
+    pkey.add_exp_assign(&secp256k1, tweak)?;
+    Ok(pkey.serialize())
+}
@@ -0,0 +1,14 @@
+//! Shared symmetric-cipher helpers used by the `keystore` and `ecies`
+//! modules.
+
+use aes_ctr::stream_cipher::generic_array::GenericArray;
+use aes_ctr::stream_cipher::{NewStreamCipher, StreamCipher};
+use aes_ctr::Aes128Ctr;
+
+/// Encrypts or decrypts `data` in place with AES-128-CTR under `key`/`iv`.
+pub fn aes_128_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) {
+    let key = GenericArray::from_slice(key);
+    let iv = GenericArray::from_slice(iv);
+    let mut cipher = Aes128Ctr::new(key, iv);
+    cipher.apply_keystream(data);
+}
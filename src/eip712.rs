@@ -0,0 +1,245 @@
+//! EIP-712 typed-data hashing and signing, on top of the hash-signing
+//! primitive in `private_key`.
+
+use failure::Error;
+use private_key::PrivateKey;
+use sha3::{Digest, Keccak256};
+use signature::Signature;
+
+/// One field of an EIP-712 struct's `encodeData`, in declaration order.
+pub enum Eip712Value<'a> {
+    /// An already-padded 32-byte word: atomics (`uint*`, `bytes32`,
+    /// `address`, `bool`, ...) are left/right-padded to this before
+    /// hashing.
+    Word([u8; 32]),
+    /// A dynamic `string`/`bytes` value, replaced by its keccak256 when
+    /// folded into `encodeData`.
+    Dynamic(&'a [u8]),
+    /// A nested struct, replaced by its `hash_struct()`.
+    Struct(Box<dyn Eip712>),
+}
+
+/// Describes an EIP-712 struct well enough to compute `hashStruct`.
+pub trait Eip712 {
+    /// The struct's type string, e.g.
+    /// `"Mail(Person from,Person to,string contents)"`. Per the spec,
+    /// any struct types it references must already be appended,
+    /// alphabetically sorted by name.
+    fn encode_type(&self) -> String;
+
+    /// This struct's fields, in declaration order, ready for `encodeData`.
+    fn encode_data(&self) -> Vec<Eip712Value>;
+
+    /// `typeHash = keccak256(encodeType)`
+    fn type_hash(&self) -> [u8; 32] {
+        keccak256(self.encode_type().as_bytes())
+    }
+
+    /// `hashStruct(s) = keccak256(typeHash || encodeData(s))`
+    fn hash_struct(&self) -> [u8; 32] {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&self.type_hash());
+        for field in self.encode_data() {
+            match field {
+                Eip712Value::Word(word) => buf.extend_from_slice(&word),
+                Eip712Value::Dynamic(bytes) => buf.extend_from_slice(&keccak256(bytes)),
+                Eip712Value::Struct(s) => buf.extend_from_slice(&s.hash_struct()),
+            }
+        }
+        keccak256(&buf)
+    }
+}
+
+/// The `EIP712Domain` struct every typed-data signature is bound to.
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: [u8; 20],
+}
+
+impl Eip712 for Eip712Domain {
+    fn encode_type(&self) -> String {
+        "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"
+            .to_owned()
+    }
+
+    fn encode_data(&self) -> Vec<Eip712Value> {
+        let mut chain_id_word = [0u8; 32];
+        chain_id_word[24..].copy_from_slice(&self.chain_id.to_be_bytes());
+
+        let mut verifying_contract_word = [0u8; 32];
+        verifying_contract_word[12..].copy_from_slice(&self.verifying_contract);
+
+        vec![
+            Eip712Value::Dynamic(self.name.as_bytes()),
+            Eip712Value::Dynamic(self.version.as_bytes()),
+            Eip712Value::Word(chain_id_word),
+            Eip712Value::Word(verifying_contract_word),
+        ]
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let digest = Keccak256::digest(data);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&digest);
+    out
+}
+
+/// Computes the final EIP-712 signing digest:
+/// `keccak256(0x1901 || domainSeparator || hashStruct(message))`.
+pub fn signing_hash(domain: &Eip712Domain, message: &dyn Eip712) -> [u8; 32] {
+    let mut buf = Vec::with_capacity(2 + 32 + 32);
+    buf.extend_from_slice(&[0x19, 0x01]);
+    buf.extend_from_slice(&domain.hash_struct());
+    buf.extend_from_slice(&message.hash_struct());
+    keccak256(&buf)
+}
+
+impl PrivateKey {
+    /// Signs EIP-712 typed `message` under `domain`.
+    pub fn sign_typed_data(
+        &self,
+        domain: &Eip712Domain,
+        message: &dyn Eip712,
+    ) -> Result<Signature, Error> {
+        self.sign_hash(&signing_hash(domain, message))
+    }
+}
+
+#[test]
+fn mail_example_recovers_to_signer() {
+    use private_key::PrivateKey;
+
+    struct Mail {
+        contents: String,
+    }
+
+    impl Eip712 for Mail {
+        fn encode_type(&self) -> String {
+            "Mail(string contents)".to_owned()
+        }
+
+        fn encode_data(&self) -> Vec<Eip712Value> {
+            vec![Eip712Value::Dynamic(self.contents.as_bytes())]
+        }
+    }
+
+    let domain = Eip712Domain {
+        name: "Clarity Test".to_owned(),
+        version: "1".to_owned(),
+        chain_id: 1,
+        verifying_contract: [0x11u8; 20],
+    };
+    let mail = Mail {
+        contents: "Hello, Bob!".to_owned(),
+    };
+
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+
+    let sig = key.sign_typed_data(&domain, &mail).unwrap();
+    let hash = signing_hash(&domain, &mail);
+    assert_eq!(sig.recover(&hash).unwrap(), key.to_public_key().unwrap());
+}
+
+#[test]
+fn matches_eip712_spec_mail_example() {
+    use utils::hex_str_to_bytes;
+
+    // The `Mail` example from the EIP-712 spec itself:
+    // https://eips.ethereum.org/EIPS/eip-712
+    struct Person {
+        name: String,
+        wallet: [u8; 20],
+    }
+
+    impl Eip712 for Person {
+        fn encode_type(&self) -> String {
+            "Person(string name,address wallet)".to_owned()
+        }
+
+        fn encode_data(&self) -> Vec<Eip712Value> {
+            let mut wallet_word = [0u8; 32];
+            wallet_word[12..].copy_from_slice(&self.wallet);
+            vec![
+                Eip712Value::Dynamic(self.name.as_bytes()),
+                Eip712Value::Word(wallet_word),
+            ]
+        }
+    }
+
+    struct Mail {
+        from: Person,
+        to: Person,
+        contents: String,
+    }
+
+    impl Eip712 for Mail {
+        fn encode_type(&self) -> String {
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+                .to_owned()
+        }
+
+        fn encode_data(&self) -> Vec<Eip712Value> {
+            vec![
+                Eip712Value::Struct(Box::new(Person {
+                    name: self.from.name.clone(),
+                    wallet: self.from.wallet,
+                })),
+                Eip712Value::Struct(Box::new(Person {
+                    name: self.to.name.clone(),
+                    wallet: self.to.wallet,
+                })),
+                Eip712Value::Dynamic(self.contents.as_bytes()),
+            ]
+        }
+    }
+
+    fn address(hex: &str) -> [u8; 20] {
+        let bytes = hex_str_to_bytes(hex).unwrap();
+        let mut out = [0u8; 20];
+        out.copy_from_slice(&bytes);
+        out
+    }
+
+    fn hash32(hex: &str) -> [u8; 32] {
+        let bytes = hex_str_to_bytes(hex).unwrap();
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&bytes);
+        out
+    }
+
+    let domain = Eip712Domain {
+        name: "Ether Mail".to_owned(),
+        version: "1".to_owned(),
+        chain_id: 1,
+        verifying_contract: address("cccccccccccccccccccccccccccccccccccccccc"),
+    };
+    let mail = Mail {
+        from: Person {
+            name: "Cow".to_owned(),
+            wallet: address("cd2a3d9f938e13cd947ec05abc7fe734df8dd826"),
+        },
+        to: Person {
+            name: "Bob".to_owned(),
+            wallet: address("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+        },
+        contents: "Hello, Bob!".to_owned(),
+    };
+
+    assert_eq!(
+        domain.hash_struct(),
+        hash32("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+    );
+    assert_eq!(
+        mail.hash_struct(),
+        hash32("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+    );
+    assert_eq!(
+        signing_hash(&domain, &mail),
+        hash32("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+    );
+}
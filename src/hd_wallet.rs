@@ -0,0 +1,306 @@
+//! BIP-32 hierarchical deterministic key derivation, used to derive
+//! standard Ethereum accounts (e.g. `m/44'/60'/0'/0/0`) from a seed.
+
+use backend;
+use constants::SECPK1N;
+use failure::Error;
+use hmac::{Hmac, Mac};
+use num256::Uint256;
+use num_traits::Zero;
+use private_key::PrivateKey;
+use sha2::Sha512;
+use std::str::FromStr;
+use utils::hex_str_to_bytes;
+
+type HmacSha512 = Hmac<Sha512>;
+
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum DerivationError {
+    #[fail(display = "Derivation path must start with \"m\"")]
+    MissingRootError,
+    #[fail(display = "Invalid child number in derivation path")]
+    InvalidChildNumberError,
+    #[fail(display = "Derived key or chain code is outside the valid range, pick another index")]
+    InvalidChildKeyError,
+    #[fail(display = "Hardened children cannot be derived from a public key alone")]
+    HardenedFromPublicError,
+}
+
+/// A single index in a BIP-32 derivation path, e.g. `44'` or `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildNumber(pub u32);
+
+impl ChildNumber {
+    pub fn is_hardened(self) -> bool {
+        self.0 >= HARDENED_OFFSET
+    }
+}
+
+impl FromStr for ChildNumber {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hardened = s.ends_with('\'') || s.ends_with('h') || s.ends_with('H');
+        let digits = if hardened { &s[..s.len() - 1] } else { s };
+
+        let index: u32 = digits
+            .parse()
+            .map_err(|_| DerivationError::InvalidChildNumberError)?;
+        if index >= HARDENED_OFFSET {
+            return Err(DerivationError::InvalidChildNumberError.into());
+        }
+
+        Ok(ChildNumber(if hardened {
+            index + HARDENED_OFFSET
+        } else {
+            index
+        }))
+    }
+}
+
+/// A parsed BIP-32 path, e.g. `m/44'/60'/0'/0/0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(DerivationError::MissingRootError.into());
+        }
+
+        let children = parts
+            .map(|part| part.parse())
+            .collect::<Result<Vec<ChildNumber>, Error>>()?;
+        Ok(DerivationPath(children))
+    }
+}
+
+/// A BIP-32 extended private key: a `PrivateKey` plus the chain code
+/// needed to derive its children.
+#[derive(Debug, Clone, Copy)]
+pub struct ExtendedPrivateKey {
+    private_key: PrivateKey,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedPrivateKey {
+    /// Derives the BIP-32 master key from a seed (e.g. the output of a
+    /// BIP-39 mnemonic): `HMAC-SHA512("Bitcoin seed", seed)`, whose left
+    /// 32 bytes are the key and right 32 bytes are the chain code.
+    pub fn master(seed: &[u8]) -> Result<ExtendedPrivateKey, Error> {
+        let mut mac =
+            HmacSha512::new_varkey(b"Bitcoin seed").expect("HMAC accepts any key length");
+        mac.input(seed);
+        let hash = mac.result().code();
+
+        let mut key_bytes = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        key_bytes.copy_from_slice(&hash[0..32]);
+        chain_code.copy_from_slice(&hash[32..64]);
+
+        let private_key = PrivateKey::from(key_bytes);
+        // Validates the key is non-zero and below the curve order.
+        private_key.to_public_key()?;
+
+        Ok(ExtendedPrivateKey {
+            private_key,
+            chain_code,
+        })
+    }
+
+    pub fn private_key(&self) -> PrivateKey {
+        self.private_key
+    }
+
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    pub fn public_key(&self) -> Result<ExtendedPublicKey, Error> {
+        let public_key = backend::public_key_compressed(&self.private_key.to_bytes())?;
+
+        Ok(ExtendedPublicKey {
+            public_key,
+            chain_code: self.chain_code,
+        })
+    }
+
+    /// Derives the child extended private key at `child` per BIP-32 CKD:
+    /// hardened children mix in the parent's private key, normal
+    /// children mix in the parent's compressed public key.
+    pub fn derive_child(&self, child: ChildNumber) -> Result<ExtendedPrivateKey, Error> {
+        let mut mac =
+            HmacSha512::new_varkey(&self.chain_code).expect("HMAC accepts any key length");
+        if child.is_hardened() {
+            mac.input(&[0x00]);
+            mac.input(&self.private_key.to_bytes());
+        } else {
+            let pkey = backend::public_key_compressed(&self.private_key.to_bytes())?;
+            mac.input(&pkey);
+        }
+        mac.input(&child.0.to_be_bytes());
+        let hash = mac.result().code();
+
+        let il = Uint256::from_bytes_be(&hash[0..32]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&hash[32..64]);
+
+        if il >= *SECPK1N {
+            return Err(DerivationError::InvalidChildKeyError.into());
+        }
+
+        let k_par = Uint256::from_bytes_be(&self.private_key.to_bytes());
+        let child_key = (il + k_par) % SECPK1N.clone();
+        if child_key == Uint256::zero() {
+            return Err(DerivationError::InvalidChildKeyError.into());
+        }
+
+        let child_bytes: [u8; 32] = child_key.into();
+        Ok(ExtendedPrivateKey {
+            private_key: PrivateKey::from(child_bytes),
+            chain_code,
+        })
+    }
+
+    /// Walks `path` from this key as the root, returning the resulting
+    /// leaf `PrivateKey`.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<PrivateKey, Error> {
+        let mut current = *self;
+        for child in &path.0 {
+            current = current.derive_child(*child)?;
+        }
+        Ok(current.private_key)
+    }
+}
+
+/// A BIP-32 extended public key: a compressed public key plus the chain
+/// code needed to derive its non-hardened children.
+#[derive(Debug, Clone, Copy)]
+pub struct ExtendedPublicKey {
+    public_key: [u8; 33],
+    chain_code: [u8; 32],
+}
+
+impl ExtendedPublicKey {
+    pub fn public_key_bytes(&self) -> [u8; 33] {
+        self.public_key
+    }
+
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    /// Derives the normal (non-hardened) child at `child` without
+    /// needing the private key; hardened children are impossible to
+    /// derive from a public key alone.
+    pub fn derive_child(&self, child: ChildNumber) -> Result<ExtendedPublicKey, Error> {
+        if child.is_hardened() {
+            return Err(DerivationError::HardenedFromPublicError.into());
+        }
+
+        let mut mac =
+            HmacSha512::new_varkey(&self.chain_code).expect("HMAC accepts any key length");
+        mac.input(&self.public_key);
+        mac.input(&child.0.to_be_bytes());
+        let hash = mac.result().code();
+
+        let il = Uint256::from_bytes_be(&hash[0..32]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&hash[32..64]);
+
+        if il >= *SECPK1N {
+            return Err(DerivationError::InvalidChildKeyError.into());
+        }
+
+        let il_bytes: [u8; 32] = il.into();
+        let child_pubkey = backend::tweak_add_compressed_public_key(&self.public_key, &il_bytes)
+            .map_err(|_| DerivationError::InvalidChildKeyError)?;
+
+        Ok(ExtendedPublicKey {
+            public_key: child_pubkey,
+            chain_code,
+        })
+    }
+}
+
+#[test]
+fn parses_standard_ethereum_path() {
+    let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+    assert_eq!(
+        path,
+        DerivationPath(vec![
+            ChildNumber(44 + HARDENED_OFFSET),
+            ChildNumber(60 + HARDENED_OFFSET),
+            ChildNumber(0 + HARDENED_OFFSET),
+            ChildNumber(0),
+            ChildNumber(0),
+        ])
+    );
+}
+
+#[test]
+#[should_panic]
+fn rejects_path_without_root() {
+    let _path: DerivationPath = "44'/60'/0'/0/0".parse().unwrap();
+}
+
+#[test]
+fn matches_bip32_test_vector_1() {
+    // BIP-32 standard test vector 1: https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki
+    fn hex32(s: &str) -> [u8; 32] {
+        let bytes = hex_str_to_bytes(s).unwrap();
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&bytes);
+        out
+    }
+
+    let seed = hex_str_to_bytes("000102030405060708090a0b0c0d0e0f").unwrap();
+    let master = ExtendedPrivateKey::master(&seed).unwrap();
+    assert_eq!(
+        master.private_key.to_bytes(),
+        hex32("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35")
+    );
+    assert_eq!(
+        master.chain_code,
+        hex32("873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508")
+    );
+
+    // Chain m/0'
+    let child = master.derive_child(ChildNumber(HARDENED_OFFSET)).unwrap();
+    assert_eq!(
+        child.private_key.to_bytes(),
+        hex32("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea")
+    );
+    assert_eq!(
+        child.chain_code,
+        hex32("47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141")
+    );
+
+    // Chain m/0'/1
+    let grandchild = child.derive_child(ChildNumber(1)).unwrap();
+    assert_eq!(
+        grandchild.private_key.to_bytes(),
+        hex32("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368")
+    );
+    assert_eq!(
+        grandchild.chain_code,
+        hex32("2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19")
+    );
+}
+
+#[test]
+fn derives_deterministic_child_keys() {
+    let seed = [0x42u8; 32];
+    let master = ExtendedPrivateKey::master(&seed).unwrap();
+    let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+
+    let key_a = master.derive_path(&path).unwrap();
+    let key_b = master.derive_path(&path).unwrap();
+    assert_eq!(key_a, key_b);
+    assert!(key_a.to_public_key().is_ok());
+}
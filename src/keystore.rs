@@ -0,0 +1,273 @@
+//! Web3 Secret Storage (keystore v3) encryption and decryption, compatible
+//! with keyfiles produced by geth and MetaMask.
+
+use cipher::aes_128_ctr;
+use failure::Error;
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use private_key::PrivateKey;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use scrypt::{scrypt, ScryptParams};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use subtle::ConstantTimeEq;
+use utils::{bytes_to_hex_str, hex_str_to_bytes};
+use uuid::Uuid;
+
+#[derive(Fail, Debug, PartialEq)]
+pub enum KeystoreError {
+    #[fail(display = "MAC mismatch: incorrect passphrase or corrupted keystore")]
+    InvalidMacError,
+    #[fail(display = "Unsupported or invalid KDF parameters in keystore")]
+    InvalidKdfParamsError,
+    #[fail(display = "Invalid cipher parameters in keystore")]
+    InvalidCipherParamsError,
+}
+
+/// Which KDF to use when deriving the symmetric key from a passphrase.
+#[derive(Debug, Clone, Copy)]
+pub enum Kdf {
+    Scrypt { n: u32, r: u32, p: u32 },
+    Pbkdf2 { c: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kdf", content = "kdfparams", rename_all = "lowercase")]
+pub enum KdfParams {
+    Scrypt {
+        n: u32,
+        r: u32,
+        p: u32,
+        dklen: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        c: u32,
+        dklen: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoSection {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub mac: String,
+    #[serde(flatten)]
+    pub kdf: KdfParams,
+}
+
+/// The top-level `version: 3` Web3 Secret Storage document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u8,
+    pub id: String,
+    pub address: String,
+    pub crypto: CryptoSection,
+}
+
+const DKLEN: usize = 32;
+
+/// Derives `dklen` bytes of key material from `passphrase` using `kdf`.
+///
+/// `dklen` must be at least 32: the AES key and MAC key are sliced out
+/// of the derived bytes at fixed offsets (`[0..16]` and `[16..32]`) per
+/// the keystore v3 spec, so a shorter `dklen` can never produce a valid
+/// keyfile and is rejected rather than silently truncated against.
+fn derive_key(passphrase: &[u8], salt: &[u8], kdf: Kdf, dklen: usize) -> Result<Vec<u8>, Error> {
+    if dklen < 32 {
+        return Err(KeystoreError::InvalidKdfParamsError.into());
+    }
+
+    let mut derived = vec![0u8; dklen];
+    match kdf {
+        Kdf::Scrypt { n, r, p } => {
+            if !n.is_power_of_two() {
+                return Err(KeystoreError::InvalidKdfParamsError.into());
+            }
+            let log_n = n.trailing_zeros() as u8;
+            let params =
+                ScryptParams::new(log_n, r, p).map_err(|_| KeystoreError::InvalidKdfParamsError)?;
+            scrypt(passphrase, salt, &params, &mut derived)
+                .map_err(|_| KeystoreError::InvalidKdfParamsError)?;
+        }
+        Kdf::Pbkdf2 { c } => {
+            pbkdf2::<Hmac<Sha256>>(passphrase, salt, c as usize, &mut derived);
+        }
+    }
+    Ok(derived)
+}
+
+/// Encrypts `key` under `passphrase`, using `kdf` to derive the
+/// symmetric key, AES-128-CTR for encryption and
+/// `keccak256(derived[16..32] || ciphertext)` as the MAC.
+pub fn encrypt(key: &PrivateKey, passphrase: &[u8], kdf: Kdf) -> Result<Keystore, Error> {
+    let mut rng = OsRng::new().expect("Failed to open OS CSPRNG");
+
+    let mut salt = [0u8; 32];
+    rng.fill_bytes(&mut salt);
+    let derived = derive_key(passphrase, &salt, kdf, DKLEN)?;
+
+    let mut iv = [0u8; 16];
+    rng.fill_bytes(&mut iv);
+
+    let mut ciphertext = key.to_bytes().to_vec();
+    aes_128_ctr(&derived[0..16], &iv, &mut ciphertext);
+
+    let mut mac_input = Vec::with_capacity(16 + ciphertext.len());
+    mac_input.extend_from_slice(&derived[16..32]);
+    mac_input.extend_from_slice(&ciphertext);
+    let mac = Keccak256::digest(&mac_input);
+
+    let address = key.to_public_key()?;
+    let salt_hex = bytes_to_hex_str(&salt);
+
+    let kdf_params = match kdf {
+        Kdf::Scrypt { n, r, p } => KdfParams::Scrypt {
+            n,
+            r,
+            p,
+            dklen: DKLEN as u32,
+            salt: salt_hex,
+        },
+        Kdf::Pbkdf2 { c } => KdfParams::Pbkdf2 {
+            c,
+            dklen: DKLEN as u32,
+            prf: "hmac-sha256".to_owned(),
+            salt: salt_hex,
+        },
+    };
+
+    Ok(Keystore {
+        version: 3,
+        id: Uuid::new_v4().to_string(),
+        address: bytes_to_hex_str(&address.as_bytes()),
+        crypto: CryptoSection {
+            cipher: "aes-128-ctr".to_owned(),
+            cipherparams: CipherParams {
+                iv: bytes_to_hex_str(&iv),
+            },
+            ciphertext: bytes_to_hex_str(&ciphertext),
+            mac: bytes_to_hex_str(&mac),
+            kdf: kdf_params,
+        },
+    })
+}
+
+/// Decrypts `keystore` with `passphrase`, verifying the MAC in constant
+/// time before decrypting.
+pub fn decrypt(keystore: &Keystore, passphrase: &[u8]) -> Result<PrivateKey, Error> {
+    let derived = match &keystore.crypto.kdf {
+        KdfParams::Scrypt {
+            n, r, p, dklen, salt,
+        } => {
+            let salt = hex_str_to_bytes(salt)?;
+            derive_key(
+                passphrase,
+                &salt,
+                Kdf::Scrypt { n: *n, r: *r, p: *p },
+                *dklen as usize,
+            )?
+        }
+        KdfParams::Pbkdf2 { c, dklen, salt, .. } => {
+            let salt = hex_str_to_bytes(salt)?;
+            derive_key(passphrase, &salt, Kdf::Pbkdf2 { c: *c }, *dklen as usize)?
+        }
+    };
+
+    let ciphertext = hex_str_to_bytes(&keystore.crypto.ciphertext)?;
+
+    let mut mac_input = Vec::with_capacity(16 + ciphertext.len());
+    mac_input.extend_from_slice(&derived[16..32]);
+    mac_input.extend_from_slice(&ciphertext);
+    let expected_mac = Keccak256::digest(&mac_input);
+    let actual_mac = hex_str_to_bytes(&keystore.crypto.mac)?;
+
+    if expected_mac.as_slice().ct_eq(&actual_mac).unwrap_u8() != 1 {
+        return Err(KeystoreError::InvalidMacError.into());
+    }
+
+    let iv = hex_str_to_bytes(&keystore.crypto.cipherparams.iv)?;
+    if iv.len() != 16 {
+        return Err(KeystoreError::InvalidCipherParamsError.into());
+    }
+    let mut plaintext = ciphertext;
+    aes_128_ctr(&derived[0..16], &iv, &mut plaintext);
+
+    PrivateKey::from_slice(&plaintext)
+}
+
+impl fmt::Display for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+#[test]
+fn encrypt_decrypt_roundtrip_scrypt() {
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+
+    let keystore = encrypt(
+        &key,
+        b"hunter2",
+        Kdf::Scrypt {
+            n: 1024,
+            r: 8,
+            p: 1,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(decrypt(&keystore, b"hunter2").unwrap(), key);
+    assert!(decrypt(&keystore, b"wrong password").is_err());
+}
+
+#[test]
+fn decrypt_rejects_malformed_iv_length() {
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+
+    // The MAC only covers `derived[16..32] || ciphertext`, not the iv, so
+    // swapping in a malformed-length iv after encryption still passes the
+    // MAC check and must be caught explicitly rather than panicking in
+    // `aes_128_ctr`.
+    let mut keystore = encrypt(&key, b"hunter2", Kdf::Pbkdf2 { c: 1024 }).unwrap();
+    keystore.crypto.cipherparams.iv = "deadbeef".to_owned();
+
+    assert!(decrypt(&keystore, b"hunter2").is_err());
+}
+
+#[test]
+fn decrypt_rejects_non_power_of_two_n() {
+    assert!(derive_key(b"hunter2", &[0u8; 32], Kdf::Scrypt { n: 1000, r: 8, p: 1 }, 32).is_err());
+}
+
+#[test]
+fn decrypt_rejects_short_dklen() {
+    assert!(derive_key(b"hunter2", &[0u8; 32], Kdf::Pbkdf2 { c: 1024 }, 16).is_err());
+}
+
+#[test]
+fn encrypt_decrypt_roundtrip_pbkdf2() {
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+
+    let keystore = encrypt(&key, b"hunter2", Kdf::Pbkdf2 { c: 1024 }).unwrap();
+
+    assert_eq!(decrypt(&keystore, b"hunter2").unwrap(), key);
+    assert!(decrypt(&keystore, b"wrong password").is_err());
+}
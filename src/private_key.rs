@@ -1,8 +1,12 @@
 use address::Address;
+use backend;
+use constants::SECPK1N;
 use error::ClarityError;
 use failure::Error;
-use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use num256::Uint256;
+use num_traits::Zero;
 use sha3::{Digest, Keccak256};
+use signature::Signature;
 use std::str::FromStr;
 use utils::{hex_str_to_bytes, ByteDecodeError};
 
@@ -50,6 +54,27 @@ impl PrivateKey {
         Ok(PrivateKey(res))
     }
 
+    /// Draws a fresh private key from a CSPRNG, rejecting candidates that
+    /// are zero or `>= SECPK1N` and re-sampling until a valid secp256k1
+    /// secret key is found, matching the validation rust-secp256k1 does
+    /// on `SecretKey` construction.
+    pub fn generate() -> PrivateKey {
+        use rand::rngs::OsRng;
+        use rand::RngCore;
+
+        let mut rng = OsRng::new().expect("Failed to open OS CSPRNG");
+        loop {
+            let mut candidate = [0u8; 32];
+            rng.fill_bytes(&mut candidate);
+
+            let as_uint = Uint256::from_bytes_be(&candidate);
+            if as_uint == Uint256::zero() || as_uint >= *SECPK1N {
+                continue;
+            }
+            return PrivateKey(candidate);
+        }
+    }
+
     pub fn to_bytes(&self) -> [u8; 32] {
         self.0
     }
@@ -58,22 +83,69 @@ impl PrivateKey {
     ///
     /// This is well explained in the EthereumYellow Paper Appendix F.
     pub fn to_public_key(&self) -> Result<Address, Error> {
-        let secp256k1 = Secp256k1::new();
-        let sk = SecretKey::from_slice(&secp256k1, &self.0)?;
-        let pkey = PublicKey::from_secret_key(&secp256k1, &sk);
-        // TODO: This part is duplicated with sender code.
-
-        // Serialize the recovered public key in uncompressed format
-        let pkey = pkey.serialize_uncompressed();
-        assert_eq!(pkey.len(), 65);
-        if pkey[1..].to_vec() == [0x00u8; 64].to_vec() {
-            return Err(ClarityError::ZeroPrivKey.into());
-        }
+        let pkey = self.to_public_key_bytes()?;
         // Finally an address is last 20 bytes of a hash of the public key.
         let sender = Keccak256::digest(&pkey[1..]);
         debug_assert_eq!(sender.len(), 32);
         Ok(Address::from(&sender[12..]))
     }
+
+    /// Returns the uncompressed secp256k1 public key (`0x04 || x || y`)
+    /// backing this private key, for callers that need the raw curve
+    /// point rather than the Keccak256-derived `Address`.
+    pub fn to_public_key_bytes(&self) -> Result<[u8; 65], Error> {
+        backend::public_key_bytes(&self.0)
+    }
+
+    /// Signs a 32-byte hash with this private key, producing a recoverable
+    /// ECDSA `Signature` with `v = recovery_id + 27`.
+    ///
+    /// The signature is normalized to low-`s` as required by EIP-2 /
+    /// Homestead: if the raw `s` is greater than `SECPK1N / 2`, it is
+    /// replaced by `SECPK1N - s` and the recovery bit is flipped to match.
+    ///
+    /// Like `to_public_key`, this rejects a zero or out-of-range key
+    /// instead of handing it to the backend, which otherwise panics on
+    /// exactly the keys `PrivateKey::new()`/`from_slice` let through
+    /// unchecked.
+    pub fn sign_hash(&self, hash: &[u8; 32]) -> Result<Signature, Error> {
+        self.to_public_key_bytes()?;
+
+        let raw = backend::sign_recoverable(&self.0, hash);
+        let r = Uint256::from_bytes_be(&raw.r);
+        let s = Uint256::from_bytes_be(&raw.s);
+
+        Ok(Signature::new((raw.recovery_id as u32 + 27).into(), r, s))
+    }
+
+    /// Signs a 32-byte hash the way `sign_hash` does, but encodes `v` per
+    /// EIP-155 (`v = recovery_id + chain_id * 2 + 35`) so the resulting
+    /// signature is bound to a specific chain/network id.
+    pub fn sign_hash_with_network_id(
+        &self,
+        hash: &[u8; 32],
+        network_id: Uint256,
+    ) -> Result<Signature, Error> {
+        let sig = self.sign_hash(hash)?;
+        let recovery_id = sig.v - Uint256::from(27u32);
+        let v = recovery_id + network_id * Uint256::from(2u32) + Uint256::from(35u32);
+        Ok(Signature::new(v, sig.r, sig.s))
+    }
+
+    /// Signs `msg` per EIP-191 `personal_sign`, prefixing it with
+    /// `"\x19Ethereum Signed Message:\n" || ascii(msg.len())` before
+    /// hashing, so the digest can never collide with a raw transaction
+    /// hash or an EIP-712 digest.
+    pub fn personal_sign(&self, msg: &[u8]) -> Result<Signature, Error> {
+        let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", msg.len()).into_bytes();
+        prefixed.extend_from_slice(msg);
+
+        let digest = Keccak256::digest(&prefixed);
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&digest);
+
+        self.sign_hash(&hash)
+    }
 }
 
 #[test]
@@ -143,3 +215,58 @@ fn zero_address() {
     let key = PrivateKey::new();
     key.to_public_key().unwrap();
 }
+
+#[test]
+fn sign_and_recover_hash() {
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+    let hash = [0x42u8; 32];
+
+    let sig = key.sign_hash(&hash).unwrap();
+    assert!(sig.is_valid());
+    assert_eq!(sig.recover(&hash).unwrap(), key.to_public_key().unwrap());
+}
+
+#[test]
+fn sign_hash_rejects_zero_key() {
+    let key = PrivateKey::new();
+    assert!(key.sign_hash(&[0x42u8; 32]).is_err());
+}
+
+#[test]
+fn generate_produces_usable_keys() {
+    for _ in 0..32 {
+        let key = PrivateKey::generate();
+        assert!(key.to_public_key().is_ok());
+    }
+}
+
+#[test]
+fn personal_sign_recovers_to_own_address() {
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+
+    let sig = key.personal_sign(b"Hello, clarity!").unwrap();
+
+    let mut prefixed = b"\x19Ethereum Signed Message:\n15".to_vec();
+    prefixed.extend_from_slice(b"Hello, clarity!");
+    let digest = Keccak256::digest(&prefixed);
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&digest);
+
+    assert_eq!(sig.recover(&hash).unwrap(), key.to_public_key().unwrap());
+}
+
+#[test]
+fn sign_and_recover_hash_with_network_id() {
+    let key: PrivateKey = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+        .parse()
+        .unwrap();
+    let hash = [0x42u8; 32];
+
+    let sig = key.sign_hash_with_network_id(&hash, 1u32.into()).unwrap();
+    assert_eq!(sig.network_id(), Some(1u32.into()));
+    assert_eq!(sig.recover(&hash).unwrap(), key.to_public_key().unwrap());
+}
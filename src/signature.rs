@@ -1,3 +1,5 @@
+use address::Address;
+use backend;
 use constants::SECPK1N;
 use error::ClarityError;
 use failure::Error;
@@ -6,6 +8,7 @@ use num_traits::Zero;
 use serde::ser::SerializeTuple;
 use serde::Serialize;
 use serde::Serializer;
+use sha3::{Digest, Keccak256};
 use utils::big_endian_int_serialize;
 use utils::bytes_to_hex_str;
 
@@ -63,6 +66,35 @@ impl Signature {
         }
         Ok(())
     }
+
+    /// Recovers the address of the key that produced this signature over
+    /// `hash`, reconstructing the recovery id from `v` (plain or EIP-155)
+    /// via `network_id()`.
+    pub fn recover(&self, hash: &[u8; 32]) -> Result<Address, Error> {
+        // `v` is attacker-controlled; reject anything `network_id()`'s
+        // unchecked subtraction can't handle before doing any arithmetic.
+        if !self.is_valid() {
+            return Err(ClarityError::InvalidSignature.into());
+        }
+        if self.v != 27u32.into() && self.v != 28u32.into() && self.v < 35u32.into() {
+            return Err(ClarityError::InvalidSignature.into());
+        }
+
+        let recovery_id = match self.network_id() {
+            Some(network_id) => self.v.clone() - (network_id * 2u32.into()) - 35u32.into(),
+            None => self.v.clone() - 27u32.into(),
+        };
+        let recovery_id = *recovery_id.to_bytes_be().last().unwrap() as i32;
+
+        let r: [u8; 32] = self.r.clone().into();
+        let s: [u8; 32] = self.s.clone().into();
+        let pkey = backend::recover_public_key(hash, &r, &s, recovery_id)?;
+
+        // Mirrors the hashing done in `PrivateKey::to_public_key`.
+        let sender = Keccak256::digest(&pkey[1..]);
+        debug_assert_eq!(sender.len(), 32);
+        Ok(Address::from(&sender[12..]))
+    }
 }
 
 impl Default for Signature {
@@ -120,6 +152,12 @@ fn to_string() {
     );
 }
 
+#[test]
+fn recover_rejects_out_of_range_v_instead_of_panicking() {
+    let sig = Signature::new(1u32.into(), 2u32.into(), 3u32.into());
+    assert!(sig.recover(&[0u8; 32]).is_err());
+}
+
 #[test]
 fn to_string_with_zero_v() {
     let sig = Signature::new(0u32.into(), 2u32.into(), 3u32.into());